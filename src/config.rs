@@ -9,6 +9,12 @@ pub struct IndexConfig {
     pub schema: Schema,
     pub text_lang: TextLanguage,
     pub writer_memory: usize,
+    /// Name of a schema field of tantivy's JSON type, used as a catch-all for
+    /// incoming keys that don't map to a concrete field. Leave unset to keep
+    /// the strict schema-only behavior. Once set, its subfields can be
+    /// searched with dotted paths, e.g. `attrs.color:red`.
+    #[serde(default)]
+    pub json_field: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -40,6 +46,7 @@ mod tests {
             schema,
             text_lang: lang,
             writer_memory: 100_000_000,
+            json_field: None,
         };
 
         let config1: IndexConfig =
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use tantivy::{directory::error::OpenDirectoryError, schema::DocParsingError};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Crate-level error type. Every public `Indexer`/`IndexUpdater` method
+/// returns this instead of a raw `tantivy::Result`, so callers (and, e.g., an
+/// HTTP layer) get a stable [`Error::code`]/[`Error::status_code`] instead of
+/// having to match on opaque tantivy internals.
+#[derive(Debug)]
+pub enum Error {
+    /// The input text couldn't be parsed as the configured `InputType`.
+    ParseInput(String),
+    /// A field referenced by a query, filter, or facet isn't in the schema.
+    UnknownField(String),
+    /// A field exists but doesn't support the requested operation (e.g.
+    /// filtering a field type with no ordering).
+    SchemaMismatch(String),
+    /// A mapped/converted value couldn't be coerced to its target type.
+    Conversion { field: String, value: String },
+    /// The background writer thread's channel is gone.
+    ChannelClosed(String),
+    Io(std::io::Error),
+    Tantivy(tantivy::TantivyError),
+}
+
+impl Error {
+    /// Stable, machine-readable error code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ParseInput(_) => "parse_input",
+            Error::UnknownField(_) => "unknown_field",
+            Error::SchemaMismatch(_) => "schema_mismatch",
+            Error::Conversion { .. } => "conversion",
+            Error::ChannelClosed(_) => "channel_closed",
+            Error::Io(_) => "io",
+            Error::Tantivy(_) => "tantivy",
+        }
+    }
+
+    /// HTTP status an API layer (e.g. `server`) should respond with.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Error::ParseInput(_) => 400,
+            Error::UnknownField(_) => 400,
+            Error::SchemaMismatch(_) => 422,
+            Error::Conversion { .. } => 400,
+            Error::ChannelClosed(_) => 503,
+            Error::Io(_) => 500,
+            Error::Tantivy(_) => 500,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ParseInput(msg) => write!(f, "failed to parse input: {msg}"),
+            Error::UnknownField(field) => write!(f, "unknown field: {field}"),
+            Error::SchemaMismatch(msg) => write!(f, "schema mismatch: {msg}"),
+            Error::Conversion { field, value } => {
+                write!(f, "cannot convert field `{field}` value `{value}`")
+            }
+            Error::ChannelClosed(msg) => write!(f, "update channel closed: {msg}"),
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Tantivy(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<tantivy::TantivyError> for Error {
+    fn from(e: tantivy::TantivyError) -> Self {
+        Error::Tantivy(e)
+    }
+}
+
+impl From<OpenDirectoryError> for Error {
+    fn from(e: OpenDirectoryError) -> Self {
+        Error::Tantivy(e.into())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<DocParsingError> for Error {
+    fn from(e: DocParsingError) -> Self {
+        match e {
+            DocParsingError::NotJson(msg) => Error::ParseInput(msg),
+            DocParsingError::NoSuchFieldInSchema(field) => Error::UnknownField(field),
+            DocParsingError::ValueError(field, err) => Error::Conversion {
+                field,
+                value: err.to_string(),
+            },
+            other => Error::SchemaMismatch(other.to_string()),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::ops::Bound;
+
+use tantivy::{
+    query::{AllQuery, BooleanQuery, Occur, Query, RangeQuery, TermQuery},
+    schema::{Field, FieldType, IndexRecordOption, Schema},
+    Term,
+};
+
+use crate::error::{Error, Result};
+
+/// A single `field op value` clause parsed out of a filter string.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FilterClause {
+    pub field: String,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FilterOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Parse a filter string such as `"lang = zh AND year > 2010"` into a list of
+/// clauses, ANDed together. This is intentionally simple: no `OR`, no
+/// parentheses, just a conjunction of comparisons.
+pub(crate) fn parse_filter(input: &str) -> Result<Vec<FilterClause>> {
+    input
+        .split(" AND ")
+        .map(|clause| parse_clause(clause.trim()))
+        .collect()
+}
+
+fn parse_clause(clause: &str) -> Result<FilterClause> {
+    // longest operators first so `!=`/`<=`/`>=` aren't mistaken for `=`/`<`/`>`
+    const OPS: [(&str, FilterOp); 6] = [
+        ("!=", FilterOp::Ne),
+        ("<=", FilterOp::Le),
+        (">=", FilterOp::Ge),
+        ("=", FilterOp::Eq),
+        ("<", FilterOp::Lt),
+        (">", FilterOp::Gt),
+    ];
+
+    for (token, op) in OPS {
+        if let Some(idx) = clause.find(token) {
+            let field = clause[..idx].trim().to_string();
+            let value = clause[idx + token.len()..]
+                .trim()
+                .trim_matches('"')
+                .to_string();
+            if field.is_empty() || value.is_empty() {
+                break;
+            }
+            return Ok(FilterClause { field, op, value });
+        }
+    }
+
+    Err(Error::ParseInput(format!("invalid filter clause: {}", clause)))
+}
+
+/// Translate parsed filter clauses into a tantivy query, one leaf per clause
+/// combined with `Occur::Must`/`MustNot`.
+pub(crate) fn build_filter_query(
+    schema: &Schema,
+    clauses: &[FilterClause],
+) -> Result<BooleanQuery> {
+    let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+        let field = schema
+            .get_field(&clause.field)
+            .ok_or_else(|| Error::UnknownField(clause.field.clone()))?;
+        let occur = match clause.op {
+            FilterOp::Ne => Occur::MustNot,
+            _ => Occur::Must,
+        };
+        subqueries.push((occur, clause_to_query(schema, field, clause)?));
+    }
+    // a boolean query made up entirely of `MustNot` clauses has nothing to
+    // subtract from and matches zero docs, not "every doc except X" - give it
+    // an `AllQuery` baseline to negate against
+    if subqueries.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+        subqueries.push((Occur::Must, Box::new(AllQuery)));
+    }
+    Ok(BooleanQuery::new(subqueries))
+}
+
+fn clause_to_query(
+    schema: &Schema,
+    field: Field,
+    clause: &FilterClause,
+) -> Result<Box<dyn Query>> {
+    let field_type = schema.get_field_entry(field).field_type();
+    match (field_type, clause.op) {
+        (FieldType::Str(_), FilterOp::Eq | FilterOp::Ne) => {
+            let term = Term::from_field_text(field, &clause.value);
+            Ok(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+        }
+        (FieldType::U64(_), FilterOp::Eq | FilterOp::Ne) => {
+            let value = parse_value::<u64>(clause)?;
+            let term = Term::from_field_u64(field, value);
+            Ok(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+        }
+        (FieldType::I64(_), FilterOp::Eq | FilterOp::Ne) => {
+            let value = parse_value::<i64>(clause)?;
+            let term = Term::from_field_i64(field, value);
+            Ok(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+        }
+        (FieldType::U64(_), op) => {
+            let value = parse_value::<u64>(clause)?;
+            let (lower, upper) = match op {
+                FilterOp::Lt => (Bound::Unbounded, Bound::Excluded(value)),
+                FilterOp::Le => (Bound::Unbounded, Bound::Included(value)),
+                FilterOp::Gt => (Bound::Excluded(value), Bound::Unbounded),
+                FilterOp::Ge => (Bound::Included(value), Bound::Unbounded),
+                _ => unreachable!(),
+            };
+            Ok(Box::new(RangeQuery::new_u64_bounds(field, lower, upper)))
+        }
+        (FieldType::I64(_), op) => {
+            let value = parse_value::<i64>(clause)?;
+            let (lower, upper) = match op {
+                FilterOp::Lt => (Bound::Unbounded, Bound::Excluded(value)),
+                FilterOp::Le => (Bound::Unbounded, Bound::Included(value)),
+                FilterOp::Gt => (Bound::Excluded(value), Bound::Unbounded),
+                FilterOp::Ge => (Bound::Included(value), Bound::Unbounded),
+                _ => unreachable!(),
+            };
+            Ok(Box::new(RangeQuery::new_i64_bounds(field, lower, upper)))
+        }
+        _ => Err(Error::SchemaMismatch(format!(
+            "field `{}` does not support filtering",
+            clause.field
+        ))),
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(clause: &FilterClause) -> Result<T> {
+    clause.value.parse().map_err(|_| Error::Conversion {
+        field: clause.field.clone(),
+        value: clause.value.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::{
+        collector::Count,
+        doc,
+        schema::{FAST, INDEXED, STORED, STRING},
+        Index,
+    };
+
+    fn build_schema() -> Schema {
+        let mut builder = Schema::builder();
+        builder.add_text_field("lang", STRING | STORED);
+        builder.add_u64_field("year", INDEXED | STORED | FAST);
+        builder.build()
+    }
+
+    #[test]
+    fn parse_filter_splits_on_and() {
+        let clauses = parse_filter("lang = zh AND year > 2010").unwrap();
+        assert_eq!(
+            clauses,
+            vec![
+                FilterClause {
+                    field: "lang".into(),
+                    op: FilterOp::Eq,
+                    value: "zh".into()
+                },
+                FilterClause {
+                    field: "year".into(),
+                    op: FilterOp::Gt,
+                    value: "2010".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_clause_prefers_longest_operator() {
+        // a naive scan for "=" would split "!=" into field "lang !" / value "zh"
+        let clause = parse_clause("lang != zh").unwrap();
+        assert_eq!(clause.op, FilterOp::Ne);
+        assert_eq!(clause.field, "lang");
+    }
+
+    #[test]
+    fn all_negative_filter_matches_every_other_doc() {
+        let schema = build_schema();
+        let index = Index::create_in_ram(schema.clone());
+        let lang = schema.get_field("lang").unwrap();
+        let mut writer = index.writer(50_000_000).unwrap();
+        writer.add_document(doc!(lang => "zh")).unwrap();
+        writer.add_document(doc!(lang => "en")).unwrap();
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let clauses = parse_filter("lang != zh").unwrap();
+        let query = build_filter_query(&schema, &clauses).unwrap();
+        let count = searcher.search(&query, &Count).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn ge_range_includes_the_type_max_value() {
+        let schema = build_schema();
+        let index = Index::create_in_ram(schema.clone());
+        let year = schema.get_field("year").unwrap();
+        let mut writer = index.writer(50_000_000).unwrap();
+        writer.add_document(doc!(year => u64::MAX)).unwrap();
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let clauses = parse_filter("year >= 10").unwrap();
+        let query = build_filter_query(&schema, &clauses).unwrap();
+        let count = searcher.search(&query, &Count).unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -1,14 +1,22 @@
 use cang_jie::{CangJieTokenizer, TokenizerOption, CANG_JIE};
 use crossbeam_channel::{unbounded, Sender};
 use jieba_rs::Jieba;
-use std::{fs, ops::Deref, sync::Arc, thread};
+use std::{collections::HashMap, fs, ops::Deref, sync::Arc, thread};
 use tantivy::{
-    collector::TopDocs, directory::MmapDirectory, query::QueryParser, schema::NamedFieldDocument,
-    Index, IndexReader, ReloadPolicy, Result,
+    collector::{FacetCollector, MultiCollector, TopDocs},
+    directory::MmapDirectory,
+    query::{BooleanQuery, Occur, Query, QueryParser},
+    schema::{FieldType, NamedFieldDocument},
+    snippet::{Snippet, SnippetGenerator},
+    Index, IndexReader, ReloadPolicy,
 };
 use tracing::{info, warn};
 
-use crate::{IndexConfig, IndexUpdater, Input, TextLanguage};
+use crate::{
+    error::{Error, Result},
+    filter::{build_filter_query, parse_filter},
+    IndexConfig, IndexUpdater, Input, TextLanguage,
+};
 
 #[derive(Clone)]
 pub struct Indexer {
@@ -30,6 +38,46 @@ pub struct IndexInner {
     updater: Sender<Input>,
 }
 
+/// Options for [`Indexer::search_with`].
+pub struct SearchQuery<'a> {
+    pub query: &'a str,
+    pub fields: &'a [&'a str],
+    /// A filter expression such as `"lang = zh AND year > 2010"`.
+    pub filter: Option<&'a str>,
+    /// Fields (declared as tantivy `Facet`s in the schema) to return counts for.
+    pub facets: &'a [&'a str],
+    /// Fields to generate highlighted snippets for. Pass the same slice as
+    /// `fields` to highlight every searched field; leave empty for none.
+    pub snippet_fields: &'a [&'a str],
+    /// Max fragment length handed to tantivy's `SnippetGenerator`.
+    pub max_num_chars: usize,
+    /// Open/close markers wrapped around each highlighted term, e.g. `("<b>", "</b>")`.
+    pub highlight: (&'a str, &'a str),
+    /// Per-field boost weights applied by the query parser.
+    pub boosts: &'a [(&'a str, f32)],
+    /// Per-field fuzzy matching, as a max Levenshtein edit distance.
+    pub fuzzy: &'a [(&'a str, u8)],
+    /// When `true`, terms with no explicit `AND`/`OR` are combined with `AND`
+    /// instead of the query parser's default `OR`.
+    pub conjunction_by_default: bool,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+/// One ranked hit, with a highlighted snippet per requested field.
+pub struct Hit {
+    pub score: f32,
+    pub doc: NamedFieldDocument,
+    pub snippets: HashMap<String, String>,
+}
+
+/// Result of [`Indexer::search_with`]: the ranked hits plus, for each
+/// requested facet field, a list of value -> count.
+pub struct SearchResult {
+    pub hits: Vec<Hit>,
+    pub facets: HashMap<String, Vec<(String, u64)>>,
+}
+
 impl Indexer {
     pub fn open_or_create(config: IndexConfig) -> Result<Self> {
         let schema = config.schema.clone();
@@ -60,7 +108,12 @@ impl Indexer {
 
     pub fn get_updater(&self) -> IndexUpdater {
         let t2s = TextLanguage::Chinese(true) == self.config.text_lang;
-        IndexUpdater::new(self.updater.clone(), self.index.schema(), t2s)
+        IndexUpdater::new(
+            self.updater.clone(),
+            self.index.schema(),
+            t2s,
+            self.config.json_field.clone(),
+        )
     }
 
     pub fn reload(&self) -> Result<()> {
@@ -74,21 +127,137 @@ impl Indexer {
         limit: usize,
         offset: usize,
     ) -> Result<Vec<(f32, NamedFieldDocument)>> {
+        let result = self.search_with(SearchQuery {
+            query,
+            fields,
+            filter: None,
+            facets: &[],
+            snippet_fields: &[],
+            max_num_chars: 150,
+            highlight: ("<b>", "</b>"),
+            boosts: &[],
+            fuzzy: &[],
+            conjunction_by_default: false,
+            limit,
+            offset,
+        })?;
+        Ok(result.hits.into_iter().map(|hit| (hit.score, hit.doc)).collect())
+    }
+
+    /// Like [`Indexer::search`], but also supports a filter expression
+    /// (`"lang = zh AND year > 2010"`) and faceted counts over fields
+    /// declared as tantivy `Facet`s in the schema.
+    pub fn search_with(&self, query: SearchQuery) -> Result<SearchResult> {
         let schema = &self.config.schema;
-        let query_fields: Vec<_> = fields.iter().filter_map(|s| schema.get_field(s)).collect();
+        let query_fields: Vec<_> = if query.fields.is_empty() {
+            // mirror tantivy-cli: search every indexed text field by default
+            // instead of silently matching nothing
+            schema
+                .fields()
+                .filter(|(_, entry)| match entry.field_type() {
+                    FieldType::Str(opts) => opts.get_indexing_options().is_some(),
+                    _ => false,
+                })
+                .map(|(field, _)| field)
+                .collect()
+        } else {
+            query
+                .fields
+                .iter()
+                .map(|name| {
+                    schema
+                        .get_field(name)
+                        .ok_or_else(|| Error::UnknownField(name.to_string()))
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
 
         let searcher = self.reader.searcher();
-        let query_parser = QueryParser::for_index(&self.index, query_fields);
-        let query = query_parser.parse_query(query)?;
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit).and_offset(offset))?;
-        let mut result = Vec::with_capacity(limit);
+        let mut query_parser = QueryParser::for_index(&self.index, query_fields);
+        for (name, boost) in query.boosts {
+            let field = schema
+                .get_field(name)
+                .ok_or_else(|| Error::UnknownField((*name).to_string()))?;
+            query_parser.set_field_boost(field, *boost);
+        }
+        for (name, distance) in query.fuzzy {
+            let field = schema
+                .get_field(name)
+                .ok_or_else(|| Error::UnknownField((*name).to_string()))?;
+            query_parser.set_field_fuzzy(field, false, *distance, true);
+        }
+        if query.conjunction_by_default {
+            query_parser.set_conjunction_by_default();
+        }
+        let text_query = query_parser.parse_query(query.query)?;
+
+        let combined_query: Box<dyn Query> = match query.filter {
+            Some(filter) => {
+                let clauses = parse_filter(filter)?;
+                let filter_query = build_filter_query(schema, &clauses)?;
+                Box::new(BooleanQuery::new(vec![
+                    (Occur::Must, text_query),
+                    (Occur::Must, Box::new(filter_query)),
+                ]))
+            }
+            None => text_query,
+        };
+
+        let mut multi_collector = MultiCollector::new();
+        let top_docs_handle =
+            multi_collector.add_collector(TopDocs::with_limit(query.limit).and_offset(query.offset));
+        let mut facet_handles = Vec::with_capacity(query.facets.len());
+        for name in query.facets {
+            let field = schema
+                .get_field(name)
+                .ok_or_else(|| Error::UnknownField(name.to_string()))?;
+            let mut collector = FacetCollector::for_field(field);
+            collector.add_facet("/");
+            facet_handles.push((*name, multi_collector.add_collector(collector)));
+        }
+
+        let mut fruits = searcher.search(&*combined_query, &multi_collector)?;
+        let top_docs = top_docs_handle.extract(&mut fruits);
+
+        let mut facets = HashMap::with_capacity(facet_handles.len());
+        for (name, handle) in facet_handles {
+            let counts = handle
+                .extract(&mut fruits)
+                .get("/")
+                .map(|(facet, count)| (facet.to_string(), count))
+                .collect();
+            facets.insert(name.to_string(), counts);
+        }
+
+        // snippet generators are built from the live index so fragment offsets
+        // line up with whatever tokenizer (e.g. CangJie) indexed the field
+        let mut snippet_generators = HashMap::with_capacity(query.snippet_fields.len());
+        for name in query.snippet_fields {
+            let field = schema
+                .get_field(name)
+                .ok_or_else(|| Error::UnknownField(name.to_string()))?;
+            let mut generator = SnippetGenerator::create(&searcher, &*combined_query, field)?;
+            generator.set_max_num_chars(query.max_num_chars);
+            snippet_generators.insert(*name, generator);
+        }
+
+        let mut hits = Vec::with_capacity(query.limit);
         for (score, addr) in top_docs {
             let doc = searcher.doc(addr)?;
-            let named_doc = schema.to_named_doc(&doc);
-            result.push((score, named_doc));
+            let mut snippets = HashMap::with_capacity(snippet_generators.len());
+            for (name, generator) in &snippet_generators {
+                let snippet = generator.snippet_from_doc(&doc);
+                let (open, close) = query.highlight;
+                snippets.insert(name.to_string(), render_snippet(&snippet, open, close));
+            }
+            hits.push(Hit {
+                score,
+                doc: schema.to_named_doc(&doc),
+                snippets,
+            });
         }
 
-        Ok(result)
+        Ok(SearchResult { hits, facets })
     }
 
     pub fn num_docs(&self) -> u64 {
@@ -124,3 +293,116 @@ impl Indexer {
         }
     }
 }
+
+/// Render a tantivy `Snippet` to HTML, wrapping each highlighted range with
+/// `open`/`close` instead of the hardcoded `<b>`/`</b>` `Snippet::to_html` uses.
+fn render_snippet(snippet: &Snippet, open: &str, close: &str) -> String {
+    let fragment = snippet.fragment();
+    let mut html = String::new();
+    let mut cursor = 0;
+    for range in snippet.highlighted() {
+        html.push_str(&escape_html(&fragment[cursor..range.start]));
+        html.push_str(open);
+        html.push_str(&escape_html(&fragment[range.start..range.end]));
+        html.push_str(close);
+        cursor = range.end;
+    }
+    html.push_str(&escape_html(&fragment[cursor..]));
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InputConfig, InputType};
+    use tantivy::schema::{Schema, STORED, TEXT};
+
+    fn test_indexer() -> Indexer {
+        let mut builder = Schema::builder();
+        builder.add_text_field("title", TEXT | STORED);
+        builder.add_text_field("content", TEXT | STORED);
+        let schema = builder.build();
+        let config = IndexConfig {
+            path: None,
+            schema,
+            text_lang: TextLanguage::Western,
+            writer_memory: 50_000_000,
+            json_field: None,
+        };
+        Indexer::open_or_create(config).unwrap()
+    }
+
+    fn add_doc(indexer: &Indexer, json: &str) {
+        let mut updater = indexer.get_updater();
+        let config = InputConfig::new(InputType::Json, vec![], vec![]);
+        updater.add(json, &config).unwrap();
+        updater.commit().unwrap();
+        indexer.reload().unwrap();
+    }
+
+    fn base_query(query: &str, fields: &[&str]) -> SearchQuery<'_> {
+        SearchQuery {
+            query,
+            fields,
+            filter: None,
+            facets: &[],
+            snippet_fields: &[],
+            max_num_chars: 150,
+            highlight: ("<b>", "</b>"),
+            boosts: &[],
+            fuzzy: &[],
+            conjunction_by_default: false,
+            limit: 10,
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn search_with_empty_fields_searches_every_text_field() {
+        let indexer = test_indexer();
+        add_doc(&indexer, r#"{"title": "hello world", "content": "foo bar"}"#);
+
+        // "foo" only appears in `content`, not `title`; matching proves the
+        // empty `fields: &[]` default swept every indexed text field
+        let result = indexer.search_with(base_query("foo", &[])).unwrap();
+        assert_eq!(result.hits.len(), 1);
+    }
+
+    #[test]
+    fn search_with_unknown_field_returns_unknown_field_error() {
+        let indexer = test_indexer();
+        add_doc(&indexer, r#"{"title": "hello world", "content": "foo bar"}"#);
+
+        let err = indexer
+            .search_with(base_query("hello", &["nope"]))
+            .unwrap_err();
+        assert!(matches!(err, Error::UnknownField(field) if field == "nope"));
+    }
+
+    #[test]
+    fn search_with_renders_highlighted_snippets() {
+        // the snippet offsets must come from the same tokenizer the field was
+        // indexed with; a future tokenizer/tantivy bump that breaks that
+        // alignment should fail this test instead of silently mis-highlighting
+        let indexer = test_indexer();
+        add_doc(
+            &indexer,
+            r#"{"title": "an update", "content": "the quick brown fox jumps"}"#,
+        );
+
+        let mut query = base_query("quick", &["content"]);
+        query.snippet_fields = &["content"];
+        query.highlight = ("<em>", "</em>");
+        let result = indexer.search_with(query).unwrap();
+
+        assert_eq!(result.hits.len(), 1);
+        let snippet = result.hits[0].snippets.get("content").unwrap();
+        assert_eq!(snippet, "the <em>quick</em> brown fox jumps");
+    }
+}
@@ -1,12 +1,14 @@
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use std::{borrow::Cow, collections::HashMap, fmt};
+use std::{borrow::Cow, collections::HashMap, fmt, io::Read};
 use tantivy::{
-    schema::{DocParsingError, FieldValue, Schema, Value},
-    Document, IndexWriter, Result, Term,
+    schema::{DocParsingError, FieldType, FieldValue, Schema, Value},
+    Document, IndexWriter, Term,
 };
 
+use crate::error::{Error, Result};
+
 pub type JsonObject = serde_json::Map<String, JsonValue>;
 pub struct JsonObjects(Vec<JsonObject>);
 
@@ -23,11 +25,21 @@ pub enum ValueType {
     Number,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zlib,
+    Brotli,
+    Zstd,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct InputConfig {
     input_type: InputType,
     mapping: HashMap<String, String>,
     conversion: HashMap<String, (ValueType, ValueType)>,
+    #[serde(default)]
+    compression: Option<Compression>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -110,8 +122,47 @@ impl InputConfig {
             input_type,
             mapping,
             conversion,
+            compression: None,
         }
     }
+
+    /// Declare that bytes handed to `IndexUpdater::add_bytes`/`update_bytes`
+    /// are compressed and must be decoded before the `input_type` parser runs.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+}
+
+/// Decompress `bytes` per `config.compression` (a no-op when unset) into a
+/// UTF-8 string, ready for the existing `&str`-based ingestion pipeline.
+pub(crate) fn decompress(bytes: &[u8], config: &InputConfig) -> Result<String> {
+    let decoded = match &config.compression {
+        None => bytes.to_vec(),
+        Some(Compression::Gzip) => {
+            let mut buf = Vec::new();
+            flate2::read::GzDecoder::new(bytes)
+                .read_to_end(&mut buf)
+                .map_err(Error::Io)?;
+            buf
+        }
+        Some(Compression::Zlib) => {
+            let mut buf = Vec::new();
+            flate2::read::ZlibDecoder::new(bytes)
+                .read_to_end(&mut buf)
+                .map_err(Error::Io)?;
+            buf
+        }
+        Some(Compression::Brotli) => {
+            let mut buf = Vec::new();
+            brotli::Decompressor::new(bytes, 4096)
+                .read_to_end(&mut buf)
+                .map_err(Error::Io)?;
+            buf
+        }
+        Some(Compression::Zstd) => zstd::stream::decode_all(bytes).map_err(Error::Io)?,
+    };
+    String::from_utf8(decoded).map_err(|e| Error::ParseInput(e.to_string()))
 }
 
 impl JsonObjects {
@@ -140,7 +191,7 @@ impl JsonObjects {
             }
         };
 
-        let convert = |obj: &mut JsonObject| {
+        let convert = |obj: &mut JsonObject| -> Result<()> {
             for (k, k1) in &config.mapping {
                 match obj.remove_entry(k) {
                     Some((_, v)) => obj.insert(k1.into(), v),
@@ -154,32 +205,54 @@ impl JsonObjects {
                             obj.insert(k, JsonValue::String(n.to_string()));
                         }
                         (JsonValue::String(s), ValueType::String, ValueType::Number) => {
-                            obj.insert(k, JsonValue::Number(s.parse().unwrap()));
+                            let n = s.parse().map_err(|_| Error::Conversion {
+                                field: k.clone(),
+                                value: s.clone(),
+                            })?;
+                            obj.insert(k, JsonValue::Number(n));
                         }
                         _ => {}
                     }
                 }
             }
+            Ok(())
         };
 
         for item in data.iter_mut() {
-            convert(item);
+            convert(item)?;
         }
 
         Ok(Self(data))
     }
 
-    pub fn to_docs(&self, schema: &Schema) -> Result<Vec<Document>> {
+    /// Convert the ingested objects into tantivy `Document`s. Keys with no
+    /// matching schema field are routed into `json_field` (a catch-all field
+    /// of tantivy's JSON type) instead of erroring, when one is configured.
+    pub fn to_docs(&self, schema: &Schema, json_field: Option<&str>) -> Result<Vec<Document>> {
         let obj2doc = |obj: &JsonObject| -> Result<Document> {
             let mut doc = Document::default();
+            let mut overflow = JsonObject::new();
             for (field_name, json_value) in obj.iter() {
-                let field = schema
-                    .get_field(field_name)
-                    .ok_or_else(|| DocParsingError::NoSuchFieldInSchema(field_name.clone()))?;
+                let field = match schema.get_field(field_name) {
+                    Some(field) => field,
+                    None => {
+                        overflow.insert(field_name.clone(), json_value.clone());
+                        continue;
+                    }
+                };
                 let field_entry = schema.get_field_entry(field);
                 let field_type = field_entry.field_type();
-                match *json_value {
-                    JsonValue::Array(ref json_items) => {
+                match (json_value, field_type) {
+                    // a JSON field's own value may legitimately be an array or
+                    // object, so hand it to tantivy whole instead of splitting
+                    // it the way we do for multi-valued text/numeric fields
+                    (_, FieldType::JsonObject(_)) => {
+                        let value = field_type
+                            .value_from_json(json_value)
+                            .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
+                        doc.add(FieldValue::new(field, value));
+                    }
+                    (JsonValue::Array(json_items), _) => {
                         for json_item in json_items {
                             let value = field_type
                                 .value_from_json(json_item)
@@ -195,9 +268,131 @@ impl JsonObjects {
                     }
                 }
             }
+
+            if !overflow.is_empty() {
+                let field_name = json_field.ok_or_else(|| {
+                    DocParsingError::NoSuchFieldInSchema(
+                        overflow.keys().next().cloned().unwrap_or_default(),
+                    )
+                })?;
+                let field = schema
+                    .get_field(field_name)
+                    .ok_or_else(|| DocParsingError::NoSuchFieldInSchema(field_name.to_string()))?;
+                let field_type = schema.get_field_entry(field).field_type();
+                let value = field_type
+                    .value_from_json(&JsonValue::Object(overflow))
+                    .map_err(|e| DocParsingError::ValueError(field_name.to_string(), e))?;
+                doc.add(FieldValue::new(field, value));
+            }
+
             Ok(doc)
         };
         let docs: Result<Vec<_>> = self.0.par_iter().map(obj2doc).collect();
         docs
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const PAYLOAD: &[u8] = b"{\"id\": 1}";
+
+    fn config_with(compression: Compression) -> InputConfig {
+        InputConfig::new(InputType::Json, vec![], vec![]).with_compression(compression)
+    }
+
+    #[test]
+    fn decompress_is_a_noop_without_compression() {
+        let config = InputConfig::new(InputType::Json, vec![], vec![]);
+        assert_eq!(decompress(PAYLOAD, &config).unwrap(), "{\"id\": 1}");
+    }
+
+    #[test]
+    fn decompress_roundtrips_gzip() {
+        let mut encoder =
+            flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(PAYLOAD).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let config = config_with(Compression::Gzip);
+        assert_eq!(decompress(&compressed, &config).unwrap(), "{\"id\": 1}");
+    }
+
+    #[test]
+    fn decompress_roundtrips_zlib() {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(PAYLOAD).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let config = config_with(Compression::Zlib);
+        assert_eq!(decompress(&compressed, &config).unwrap(), "{\"id\": 1}");
+    }
+
+    #[test]
+    fn decompress_roundtrips_brotli() {
+        let mut compressed = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 5, 22);
+            writer.write_all(PAYLOAD).unwrap();
+        }
+
+        let config = config_with(Compression::Brotli);
+        assert_eq!(decompress(&compressed, &config).unwrap(), "{\"id\": 1}");
+    }
+
+    #[test]
+    fn decompress_roundtrips_zstd() {
+        let compressed = zstd::stream::encode_all(PAYLOAD, 0).unwrap();
+
+        let config = config_with(Compression::Zstd);
+        assert_eq!(decompress(&compressed, &config).unwrap(), "{\"id\": 1}");
+    }
+
+    #[test]
+    fn to_docs_routes_unknown_keys_into_the_json_overflow_field() {
+        use tantivy::{collector::Count, query::QueryParser, schema::STORED, schema::TEXT};
+
+        let mut builder = Schema::builder();
+        builder.add_text_field("title", TEXT | STORED);
+        builder.add_json_field("overflow", TEXT | STORED);
+        let schema = builder.build();
+
+        let config = InputConfig::new(InputType::Json, vec![], vec![]);
+        let json = r#"{"title": "a doc", "color": "red", "year": 2020}"#;
+        let objs = JsonObjects::new(json, &config, false).unwrap();
+        let docs = objs.to_docs(&schema, Some("overflow")).unwrap();
+        assert_eq!(docs.len(), 1);
+
+        let index = tantivy::Index::create_in_ram(schema.clone());
+        let mut writer = index.writer(50_000_000).unwrap();
+        for doc in docs {
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let overflow = schema.get_field("overflow").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![overflow]);
+        let query = query_parser.parse_query("overflow.color:red").unwrap();
+        let count = searcher.search(&query, &Count).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn to_docs_without_json_field_configured_errors_on_unknown_keys() {
+        use tantivy::schema::{STORED, TEXT};
+
+        let mut builder = Schema::builder();
+        builder.add_text_field("title", TEXT | STORED);
+        let schema = builder.build();
+
+        let config = InputConfig::new(InputType::Json, vec![], vec![]);
+        let json = r#"{"title": "a doc", "color": "red"}"#;
+        let objs = JsonObjects::new(json, &config, false).unwrap();
+        let err = objs.to_docs(&schema, None).unwrap_err();
+        assert!(matches!(err, Error::UnknownField(_)));
+    }
+}
@@ -1,9 +1,18 @@
 mod config;
+mod error;
+mod filter;
 mod indexer;
+mod input;
+#[cfg(feature = "server")]
+pub mod server;
 mod updater;
 
+pub(crate) use input::Input;
+
 pub use config::{IndexConfig, TextLanguage};
-pub use indexer::Indexer;
+pub use error::{Error, Result};
+pub use indexer::{Hit, Indexer, SearchQuery, SearchResult};
+pub use input::Compression;
 pub use updater::{IndexUpdater, InputConfig, InputType, ValueType};
 
 // re-exports
@@ -0,0 +1,122 @@
+//! Optional HTTP server exposing an [`Indexer`] as a MeiliSearch-style REST
+//! API. Enable with the `server` feature.
+//!
+//! The router shares a single `Indexer` (it's already `Clone` over an `Arc`)
+//! across requests and builds a fresh `IndexUpdater` per request, since
+//! updaters are just cheap channel senders.
+
+use axum::{
+    extract::{Query as AxumQuery, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{Error, Indexer, InputConfig};
+
+#[derive(Clone)]
+struct AppState {
+    indexer: Indexer,
+}
+
+/// Build the axum `Router` for a given `Indexer`. Callers mount/serve it
+/// however they like, e.g. `axum::serve(listener, server::app(indexer))`.
+pub fn app(indexer: Indexer) -> Router {
+    let state = AppState { indexer };
+    Router::new()
+        .route(
+            "/documents",
+            post(add_documents)
+                .put(update_documents)
+                .delete(clear_documents),
+        )
+        .route("/commit", post(commit))
+        .route("/search", get(search))
+        .with_state(state)
+}
+
+#[derive(Deserialize)]
+struct IngestRequest {
+    body: String,
+    config: InputConfig,
+}
+
+async fn add_documents(
+    State(state): State<AppState>,
+    Json(req): Json<IngestRequest>,
+) -> impl IntoResponse {
+    let mut updater = state.indexer.get_updater();
+    into_response(updater.add(&req.body, &req.config))
+}
+
+async fn update_documents(
+    State(state): State<AppState>,
+    Json(req): Json<IngestRequest>,
+) -> impl IntoResponse {
+    let mut updater = state.indexer.get_updater();
+    into_response(updater.update(&req.body, &req.config))
+}
+
+async fn clear_documents(State(state): State<AppState>) -> impl IntoResponse {
+    into_response(state.indexer.get_updater().clear())
+}
+
+async fn commit(State(state): State<AppState>) -> impl IntoResponse {
+    into_response(state.indexer.get_updater().commit())
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    #[serde(default)]
+    fields: Option<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+    #[serde(default)]
+    offset: usize,
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+async fn search(
+    State(state): State<AppState>,
+    AxumQuery(params): AxumQuery<SearchParams>,
+) -> impl IntoResponse {
+    // works against the latest reloaded reader: commits auto-reload the
+    // index reader, just with a delay of up to a few hundred milliseconds
+    let fields: Vec<_> = params
+        .fields
+        .as_deref()
+        .map(|s| s.split(',').collect())
+        .unwrap_or_default();
+    match state
+        .indexer
+        .search(&params.q, &fields, params.limit, params.offset)
+    {
+        Ok(hits) => Json(
+            hits.into_iter()
+                .map(|(score, doc)| json!({ "score": score, "doc": doc }))
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+fn into_response(result: crate::Result<()>) -> impl IntoResponse {
+    match result {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+fn error_response(e: Error) -> axum::response::Response {
+    let status =
+        StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, Json(json!({ "code": e.code(), "message": e.to_string() }))).into_response()
+}
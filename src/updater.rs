@@ -1,52 +1,81 @@
 use crossbeam_channel::Sender;
-use tantivy::{schema::Schema, Result, TantivyError};
+use tantivy::schema::Schema;
 
-use crate::{input::JsonObjects, Input, InputConfig};
+use crate::{
+    error::{Error, Result},
+    input::{decompress, JsonObjects},
+    Input,
+};
+
+pub use crate::input::{InputConfig, InputType, ValueType};
 
 pub struct IndexUpdater {
     sender: Sender<Input>,
     t2s: bool,
     schema: Schema,
+    json_field: Option<String>,
 }
 
 impl IndexUpdater {
-    pub(crate) fn new(sender: Sender<Input>, schema: Schema, t2s: bool) -> Self {
+    pub(crate) fn new(
+        sender: Sender<Input>,
+        schema: Schema,
+        t2s: bool,
+        json_field: Option<String>,
+    ) -> Self {
         Self {
             sender,
             schema,
             t2s,
+            json_field,
         }
     }
 
     pub fn add(&mut self, text: &str, config: &InputConfig) -> Result<()> {
         let objs = JsonObjects::new(text, config, self.t2s)?;
-        let docs = objs.to_docs(&self.schema)?;
+        let docs = objs.to_docs(&self.schema, self.json_field.as_deref())?;
         let msg = Input::new_create(docs);
         self.sender
             .send(msg)
-            .map_err(|e| TantivyError::SystemError(e.to_string()))
+            .map_err(|e| Error::ChannelClosed(e.to_string()))
     }
 
     pub fn update(&mut self, text: &str, config: &InputConfig) -> Result<()> {
         let objs = JsonObjects::new(text, config, self.t2s)?;
-        let docs = objs.to_docs(&self.schema)?;
+        let docs = objs.to_docs(&self.schema, self.json_field.as_deref())?;
         let msg = Input::new_update(docs);
         self.sender
             .send(msg)
-            .map_err(|e| TantivyError::SystemError(e.to_string()))
+            .map_err(|e| Error::ChannelClosed(e.to_string()))
+    }
+
+    /// Like [`IndexUpdater::add`], but for a byte stream that's optionally
+    /// compressed per `config`'s `compression` setting (see
+    /// `InputConfig::with_compression`), so large bulk payloads don't need to
+    /// be decompressed by the caller first.
+    pub fn add_bytes(&mut self, bytes: &[u8], config: &InputConfig) -> Result<()> {
+        let text = decompress(bytes, config)?;
+        self.add(&text, config)
+    }
+
+    /// Like [`IndexUpdater::update`], but for a byte stream that's optionally
+    /// compressed (see [`IndexUpdater::add_bytes`]).
+    pub fn update_bytes(&mut self, bytes: &[u8], config: &InputConfig) -> Result<()> {
+        let text = decompress(bytes, config)?;
+        self.update(&text, config)
     }
 
     pub fn commit(&self) -> Result<()> {
         let msg = Input::new_commit();
         self.sender
             .send(msg)
-            .map_err(|e| TantivyError::SystemError(e.to_string()))
+            .map_err(|e| Error::ChannelClosed(e.to_string()))
     }
 
     pub fn clear(&self) -> Result<()> {
         let msg = Input::new_clear();
         self.sender
             .send(msg)
-            .map_err(|e| TantivyError::SystemError(e.to_string()))
+            .map_err(|e| Error::ChannelClosed(e.to_string()))
     }
 }